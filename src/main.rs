@@ -8,9 +8,34 @@ use bevy::{
 	utils::Duration,
 };
 use rand::random;
+use std::{collections::HashSet, fs, thread, time::Instant};
 
 const ARENA_WIDTH: u32 = 100;
 const ARENA_HEIGHT: u32 = 100;
+const FOOD_COLOR: Color = Color::rgb(0.9, 0.3, 0.5);
+const BONUS_FOOD_COLOR: Color = Color::rgb(0.9, 0.8, 0.2);
+const SHRINK_FOOD_COLOR: Color = Color::rgb(0.3, 0.4, 0.9);
+const BONUS_FOOD_SECONDS: f32 = 5.0;
+// Chance per move of adding a bonus or shrink food, so the rate follows game speed, not frame rate
+const SPECIAL_FOOD_CHANCE: f32 = 0.025;
+// Head plus one segment. Shrink food never takes a trail below this.
+const MIN_TRAIL_LENGTH: usize = 2;
+const MOVE_INTERVAL: f32 = 0.08;
+const FAST_FORWARD_RATE: f32 = 4.0;
+const SPRINT_SPEEDUP: f32 = 0.95;
+const SPRINT_MIN_INTERVAL: f32 = 0.03;
+const DISPLAY_SETTINGS_PATH: &str = "settings.cfg";
+const FRAME_CAPS: [Option<u32>; 4] = [None, Some(30), Some(60), Some(120)];
+const TRAIL_MIN_BRIGHTNESS: f32 = 0.3;
+const MINIMAP_SIZE: f32 = 100.0;
+const MINIMAP_MARKER_SIZE: f32 = 3.0;
+const MINIMAP_MAX_TRAIL_MARKERS: usize = 20;
+const SCORE_POPUP_SECONDS: f32 = 0.8;
+const SCORE_POPUP_RISE: f32 = 30.0;
+const VICTORY_SECONDS: f32 = 2.0;
+const NOTIFICATION_SECONDS: f32 = 2.0;
+const NOTIFICATION_SPACING: f32 = 20.0;
+const PLAYER_NAME: &str = "Player 1";
 
 struct Player {
 	name: String,
@@ -24,13 +49,139 @@ struct Position {
 
 struct PlayerSegment;
 
-struct GrowthEvent;
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum FoodKind {
+	Normal,
+	// Worth more, but disappears after BONUS_FOOD_SECONDS
+	Bonus,
+	Shrink,
+}
+
+impl FoodKind {
+	// Length change when eaten. Positive values are also scored.
+	fn value(self) -> i32 {
+		match self {
+			Self::Normal => 1,
+			Self::Bonus => 5,
+			Self::Shrink => -1,
+		}
+	}
+
+	fn color(self) -> Color {
+		match self {
+			Self::Normal => FOOD_COLOR,
+			Self::Bonus => BONUS_FOOD_COLOR,
+			Self::Shrink => SHRINK_FOOD_COLOR,
+		}
+	}
+}
+
+struct Food {
+	kind: FoodKind,
+	value: i32,
+}
+
+// Food that despawns when the timer runs out
+struct FoodLifetime(Timer);
+
+// Arena border cell. Heads crashing into a wall are handled like any other occupied cell.
+struct Wall;
+
+// Sent when a head eats food at `position`. `value` is the food's value, negative for shrink food.
+struct GrowthEvent {
+	position: Position,
+	value: i32,
+}
 
 struct GameOverEvent;
 
-#[derive(Default)]
+struct PlayerJoinedEvent {
+	name: String,
+}
+
+struct Minimap;
+
+struct MinimapMarker;
+
+// Display name of the player controlling a head
+struct PlayerName(String);
+
+// Base color of a player's trail
+struct PlayerColor(Color);
+
+// Head that crashed, and the tick it crashed on. Its trail stays on the board for the others.
+struct Eliminated {
+	death_tick: usize,
+}
+
+// Floating "+N" text over an eaten food cell
+struct ScorePopup {
+	timer: Timer,
+	origin: Vec3,
+}
+
+// Transient on-screen text, despawned when the timer runs out
+struct Notification(Timer);
+
+// Trail of a single player, attached to its head. The first entry is the head itself.
 struct PlayerSegments(Vec<Entity>);
 
+// Paces movement and growth. This is the only timer scaled when fast-forwarding.
+struct MoveTimer(Timer);
+
+impl Default for MoveTimer {
+	fn default() -> Self {
+		Self(Timer::from_seconds(MOVE_INTERVAL, true))
+	}
+}
+
+// Grace period after a (re)spawn. Heads stay put until it runs out or a direction key is freshly
+// pressed. Until that first fresh press, held keys are ignored even after the timer has run out,
+// so a key still held from the previous round can't steer the new head into a wall.
+#[derive(Default)]
+struct RespawnGrace {
+	timer: Timer,
+	needs_fresh_press: bool,
+}
+
+impl RespawnGrace {
+	fn new(seconds: f32) -> Self {
+		Self {
+			timer: Timer::from_seconds(seconds, false),
+			needs_fresh_press: true,
+		}
+	}
+
+	fn active(&self) -> bool {
+		!self.timer.finished()
+	}
+
+	fn end(&mut self) {
+		if self.active() {
+			let remaining = self.timer.duration() - self.timer.elapsed();
+			self.timer.tick(remaining);
+		}
+		self.needs_fresh_press = false;
+	}
+}
+
+// Number of moves made so far, used to order eliminations
+#[derive(Default)]
+struct TickCounter(usize);
+
+struct Standing {
+	name: String,
+	death_tick: Option<usize>,
+	points: usize,
+}
+
+// Results of the last finished round, best placed player first
+#[derive(Default)]
+struct RoundSummary {
+	standings: Vec<Standing>,
+}
+
+// Where a head's tail was before its last move. Growth appends the new segment there.
 #[derive(Default)]
 struct LastTailPosition(Option<Position>);
 
@@ -57,6 +208,7 @@ enum Direction {
 
 #[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
 pub enum PlayerMovement {
+	Tick,
     Input,
     Movement,
     Growth,
@@ -68,7 +220,9 @@ enum AppState {
 	MainMenu,
 	InGame,
 	Paused,
-	GameOver
+	GameOver,
+	ConfirmQuit,
+	Victory,
 }
 
 impl Direction {
@@ -95,20 +249,214 @@ struct GameState {
 }
 
 struct PlayerHead {
+	// Pending direction, applied on the next move
 	direction: Direction,
+	// Direction of the last move actually made. Reversal checks use this, so several key presses
+	// between two moves can't add up to a 180 degree turn.
+	committed_direction: Direction,
+}
+
+impl PlayerHead {
+	fn new(direction: Direction) -> Self {
+		Self {
+			direction,
+			committed_direction: direction,
+		}
+	}
 }
 struct Materials {
 	head_material: Handle<ColorMaterial>,
 	segment_material: Handle<ColorMaterial>,
+	wall_material: Handle<ColorMaterial>,
+	minimap_material: Handle<ColorMaterial>,
 }
 
 struct GameRules {
 	winning_score: usize,
 	max_rounds: usize,
 	max_players: usize,
+	// Heads leaving one edge come back on the opposite edge instead of hitting a border wall
+	wrap_around: bool,
+	show_minimap: bool,
+	// Seconds a freshly spawned head waits for a new key press before moving on its own
+	respawn_grace: f32,
+	// Seconds between moves
+	move_interval: f32,
+	// Last preset applied. Rules changed on their own afterwards don't update it.
+	difficulty: Difficulty,
+	mode: GameMode,
+	// Normal food kept on the board
+	food_count: usize,
+	// Turns off purely visual animation. Gameplay is the same either way.
+	reduced_motion: bool,
+}
+
+impl Default for GameRules {
+	fn default() -> Self {
+		Self {
+			max_rounds: 100,
+			winning_score: 51,
+			max_players: 4,
+			wrap_around: false,
+			show_minimap: false,
+			respawn_grace: 1.0,
+			move_interval: MOVE_INTERVAL,
+			difficulty: Difficulty::Normal,
+			mode: GameMode::Classic,
+			food_count: 3,
+			reduced_motion: false,
+		}
+	}
 }
 struct MenuData {
-    button_entity: Entity,
+    root_entity: Entity,
+}
+
+struct ConfirmQuitData {
+	root_entity: Entity,
+}
+
+struct GameOverData {
+	root_entity: Entity,
+}
+
+// Celebration shown over the frozen game before moving on to the game over screen
+struct VictorySequence(Timer);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum GameMode {
+	Classic,
+	// Trails never grow. Food only scores and speeds the game up.
+	Sprint,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum Difficulty {
+	Easy,
+	Normal,
+	Hard,
+}
+
+impl Difficulty {
+	// The selected preset is marked so the menu shows which one is active
+	fn label(self, selected: Difficulty) -> String {
+		if self == selected {
+			format!("> {:?} <", self)
+		} else {
+			format!("{:?}", self)
+		}
+	}
+}
+
+struct DifficultyButton(Difficulty);
+
+struct PlayButton;
+
+#[derive(Copy, Clone)]
+enum SettingButton {
+	VSync,
+	FrameCap,
+	ReducedMotion,
+	Minimap,
+	Mode,
+	WrapAround,
+}
+
+impl SettingButton {
+	fn label(&self, game_rules: &GameRules, display_settings: &DisplaySettings) -> String {
+		match self {
+			Self::VSync => format!("VSync: {}", if display_settings.vsync { "On" } else { "Off" }),
+			Self::FrameCap => match display_settings.frame_cap {
+				Some(cap) => format!("FPS cap: {}", cap),
+				None => "FPS cap: Off".to_string(),
+			},
+			Self::ReducedMotion => format!(
+				"Motion: {}",
+				if game_rules.reduced_motion { "Reduced" } else { "Full" }
+			),
+			Self::Minimap => format!("Minimap: {}", if game_rules.show_minimap { "On" } else { "Off" }),
+			Self::Mode => format!("Mode: {:?}", game_rules.mode),
+			Self::WrapAround => format!("Wrap: {}", if game_rules.wrap_around { "On" } else { "Off" }),
+		}
+	}
+
+	// Only display settings are saved to DISPLAY_SETTINGS_PATH, game rules reset on every launch
+	fn is_display_setting(&self) -> bool {
+		matches!(self, Self::VSync | Self::FrameCap)
+	}
+
+	fn toggle(&self, game_rules: &mut GameRules, display_settings: &mut DisplaySettings) {
+		match self {
+			Self::VSync => display_settings.vsync = !display_settings.vsync,
+			Self::FrameCap => {
+				let index = FRAME_CAPS
+					.iter()
+					.position(|cap| *cap == display_settings.frame_cap)
+					.unwrap_or(0);
+				display_settings.frame_cap = FRAME_CAPS[(index + 1) % FRAME_CAPS.len()];
+			}
+			Self::ReducedMotion => game_rules.reduced_motion = !game_rules.reduced_motion,
+			Self::Minimap => game_rules.show_minimap = !game_rules.show_minimap,
+			Self::Mode => {
+				game_rules.mode = match game_rules.mode {
+					GameMode::Classic => GameMode::Sprint,
+					GameMode::Sprint => GameMode::Classic,
+				}
+			}
+			Self::WrapAround => game_rules.wrap_around = !game_rules.wrap_around,
+		}
+	}
+}
+
+// Frame pacing options. They configure the window, which is created as soon as DefaultPlugins is
+// added, so main reads them from DISPLAY_SETTINGS_PATH before building the app. The menu writes the
+// file whenever one is toggled and the change takes effect on the next launch.
+#[derive(Copy, Clone)]
+struct DisplaySettings {
+	vsync: bool,
+	frame_cap: Option<u32>,
+}
+
+impl Default for DisplaySettings {
+	fn default() -> Self {
+		Self {
+			vsync: true,
+			frame_cap: None,
+		}
+	}
+}
+
+impl DisplaySettings {
+	fn load() -> Self {
+		let mut settings = Self::default();
+		let contents = match fs::read_to_string(DISPLAY_SETTINGS_PATH) {
+			Ok(contents) => contents,
+			Err(_) => return settings,
+		};
+		for line in contents.lines() {
+			let mut parts = line.splitn(2, '=').map(str::trim);
+			match (parts.next(), parts.next()) {
+				(Some("vsync"), Some(value)) => settings.vsync = value == "true",
+				// A cap of 0 would make frame_limiter divide by zero, treat it as no cap
+				(Some("frame_cap"), Some(value)) => {
+					settings.frame_cap = value.parse().ok().filter(|cap| *cap > 0)
+				}
+				_ => {}
+			}
+		}
+		settings
+	}
+
+	fn save(&self) {
+		let frame_cap = match self.frame_cap {
+			Some(cap) => cap.to_string(),
+			None => "off".to_string(),
+		};
+		let contents = format!("vsync={}\nframe_cap={}\n", self.vsync, frame_cap);
+		if let Err(error) = fs::write(DISPLAY_SETTINGS_PATH, contents) {
+			println!("Could not save {}: {}", DISPLAY_SETTINGS_PATH, error);
+		}
+	}
 }
 
 // SYSTEMS: Logic that runs on entities, components, and resources. These generally run once each
@@ -122,64 +470,141 @@ fn new_round_system(game_rules: Res<GameRules>, mut game_state: ResMut<GameState
 	);
 }
 
+// Cameras live for the whole app, the menu is set up again after every game
+fn setup_cameras(mut commands: Commands) {
+	commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+	commands.spawn_bundle(UiCameraBundle::default());
+}
+
 // Menu
 fn setup_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
 	mut materials: ResMut<Assets<ColorMaterial>>,
+	game_rules: Res<GameRules>,
+	display_settings: Res<DisplaySettings>,
 ) {
-    // ui camera
-	commands.spawn_bundle(OrthographicCameraBundle::new_2d());
-    //commands.spawn_bundle(UiCameraBundle::default());
-    let button_entity = commands
-        .spawn_bundle(ButtonBundle {
-            style: Style {
-                size: Size::new(Val::Px(150.0), Val::Px(65.0)),
-                // center button
-                margin: Rect::all(Val::Auto),
-                // horizontally center child text
-                justify_content: JustifyContent::Center,
-                // vertically center child text
-                align_items: AlignItems::Center,
-                ..Default::default()
-            },
-            //material: button_materials.normal.clone(),
-            ..Default::default()
-        })
-        .with_children(|parent| {
-            parent.spawn_bundle(TextBundle {
-                text: Text::with_section(
-                    "Play",
-                    TextStyle {
-                        font: asset_server.load("fonts/Chivo-Regular.ttf"),
-                        font_size: 40.0,
-                        color: Color::rgb(0.9, 0.9, 0.9),
-                    },
-                    Default::default(),
-                ),
-                ..Default::default()
-            });
-        })
-        .id();
-    commands.insert_resource(MenuData { button_entity });
+	let font: Handle<Font> = asset_server.load("fonts/Chivo-Regular.ttf");
+	let root_entity = commands
+		.spawn_bundle(NodeBundle {
+			style: Style {
+				size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+				// stack buttons top to bottom
+				flex_direction: FlexDirection::ColumnReverse,
+				justify_content: JustifyContent::Center,
+				align_items: AlignItems::Center,
+				..Default::default()
+			},
+			material: materials.add(Color::NONE.into()),
+			..Default::default()
+		})
+		.with_children(|parent| {
+			spawn_menu_button(parent, font.clone(), "Play", PlayButton);
+			for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard].iter() {
+				spawn_menu_button(
+					parent,
+					font.clone(),
+					&difficulty.label(game_rules.difficulty),
+					DifficultyButton(*difficulty),
+				);
+			}
+			let settings = [
+				SettingButton::VSync,
+				SettingButton::FrameCap,
+				SettingButton::ReducedMotion,
+				SettingButton::Minimap,
+				SettingButton::Mode,
+				SettingButton::WrapAround,
+			];
+			for setting in settings.iter() {
+				spawn_menu_button(
+					parent,
+					font.clone(),
+					&setting.label(&game_rules, &display_settings),
+					*setting,
+				);
+			}
+		})
+		.id();
+    commands.insert_resource(MenuData { root_entity });
 	commands.insert_resource(Materials {
         head_material: materials.add(Color::rgb(0.1, 0.9, 0.9).into()),
-		segment_material: materials.add(Color::rgb(0.1, 0.7, 0.7).into())
+		segment_material: materials.add(Color::rgb(0.1, 0.7, 0.7).into()),
+		wall_material: materials.add(Color::rgb(0.3, 0.3, 0.3).into()),
+		minimap_material: materials.add(Color::rgba(0.0, 0.0, 0.0, 0.6).into()),
     });
 }
 
+// Menu button with a text label. The marker component tells menu() what the button does.
+fn spawn_menu_button<T: Send + Sync + 'static>(
+	parent: &mut ChildBuilder,
+	font: Handle<Font>,
+	label: &str,
+	marker: T,
+) {
+	parent
+		.spawn_bundle(ButtonBundle {
+			style: Style {
+				size: Size::new(Val::Px(200.0), Val::Px(40.0)),
+				margin: Rect::all(Val::Px(3.0)),
+				// horizontally center child text
+				justify_content: JustifyContent::Center,
+				// vertically center child text
+				align_items: AlignItems::Center,
+				..Default::default()
+			},
+			..Default::default()
+		})
+		.insert(marker)
+		.with_children(|parent| {
+			parent.spawn_bundle(TextBundle {
+				text: Text::with_section(
+					label,
+					TextStyle {
+						font,
+						font_size: 24.0,
+						color: Color::rgb(0.9, 0.9, 0.9),
+					},
+					Default::default(),
+				),
+				..Default::default()
+			});
+		});
+}
+
 fn menu(
     mut state: ResMut<State<AppState>>,
+	mut game_rules: ResMut<GameRules>,
+	mut display_settings: ResMut<DisplaySettings>,
     mut interaction_query: Query<
-        (&Interaction, &mut Handle<ColorMaterial>),
+        (
+			&Interaction,
+			Option<&DifficultyButton>,
+			Option<&SettingButton>,
+		),
         (Changed<Interaction>, With<Button>),
     >,
+	buttons: Query<(&Children, Option<&DifficultyButton>, Option<&SettingButton>), With<Button>>,
+	mut texts: Query<&mut Text>,
 ) {
-    for (interaction, mut material) in interaction_query.iter_mut() {
+	let mut relabel = false;
+    for (interaction, difficulty, setting) in interaction_query.iter_mut() {
         match *interaction {
             Interaction::Clicked => {
-                state.set(AppState::InGame).unwrap();
-            }
+				if let Some(DifficultyButton(difficulty)) = difficulty {
+					apply_difficulty(&mut game_rules, *difficulty);
+					println!("Difficulty set to {:?}", difficulty);
+					relabel = true;
+				} else if let Some(setting) = setting {
+					setting.toggle(&mut game_rules, &mut display_settings);
+					if setting.is_display_setting() {
+						display_settings.save();
+					}
+					relabel = true;
+				} else {
+					state.set(AppState::InGame).unwrap();
+				}
+			}
             Interaction::Hovered => {
 				println!("{:?}", state.current());
             }
@@ -188,17 +613,135 @@ fn menu(
             }
         }
     }
+	// A preset changes rules that have their own buttons, so refresh every label, not just the
+	// clicked one
+	if !relabel {
+		return;
+	}
+	for (children, difficulty, setting) in buttons.iter() {
+		let label = match (difficulty, setting) {
+			(Some(DifficultyButton(difficulty)), _) => difficulty.label(game_rules.difficulty),
+			(_, Some(setting)) => setting.label(&game_rules, &display_settings),
+			_ => continue,
+		};
+		if let Ok(mut text) = texts.get_mut(children[0]) {
+			text.sections[0].value = label;
+		}
+	}
+}
+
+// Set a coherent bundle of rules for a difficulty. Wrap-around can still be toggled afterwards.
+fn apply_difficulty(rules: &mut GameRules, difficulty: Difficulty) {
+	rules.difficulty = difficulty;
+	match difficulty {
+		Difficulty::Easy => {
+			rules.move_interval = 0.12;
+			rules.wrap_around = true;
+			rules.respawn_grace = 1.5;
+		}
+		Difficulty::Normal => {
+			rules.move_interval = MOVE_INTERVAL;
+			rules.wrap_around = false;
+			rules.respawn_grace = 1.0;
+		}
+		Difficulty::Hard => {
+			rules.move_interval = 0.05;
+			rules.wrap_around = false;
+			rules.respawn_grace = 0.5;
+		}
+	}
 }
 
 fn cleanup_menu(mut commands: Commands, menu_data: Res<MenuData>) {
-    commands.entity(menu_data.button_entity).despawn_recursive();
+    commands.entity(menu_data.root_entity).despawn_recursive();
+}
+
+// Escape during a game asks for confirmation before quitting. The dialog is pushed on top of the
+// game, so none of the InGame systems run until it is popped again. This runs after start_victory,
+// so a victory queued on the same frame wins and the quit prompt is dropped.
+fn request_quit(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+	if keyboard_input.just_pressed(KeyCode::Escape) {
+		if let Err(error) = state.push(AppState::ConfirmQuit) {
+			println!("Quit prompt skipped: {:?}", error);
+		}
+	}
+}
+
+fn setup_confirm_quit(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+	mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+	let root_entity = commands
+		.spawn_bundle(NodeBundle {
+			style: Style {
+				size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+				position_type: PositionType::Absolute,
+				justify_content: JustifyContent::Center,
+				align_items: AlignItems::Center,
+				..Default::default()
+			},
+			// dim the frozen game underneath
+			material: materials.add(Color::rgba(0.0, 0.0, 0.0, 0.6).into()),
+			..Default::default()
+		})
+		.with_children(|parent| {
+			parent.spawn_bundle(TextBundle {
+				text: Text::with_section(
+					"Quit? Y/N",
+					TextStyle {
+						font: asset_server.load("fonts/Chivo-Regular.ttf"),
+						font_size: 40.0,
+						color: Color::rgb(0.9, 0.9, 0.9),
+					},
+					Default::default(),
+				),
+				..Default::default()
+			});
+		})
+		.id();
+	commands.insert_resource(ConfirmQuitData { root_entity });
+}
+
+fn confirm_quit(
+	keyboard_input: Res<Input<KeyCode>>,
+	mut state: ResMut<State<AppState>>,
+	mut app_exit_events: EventWriter<AppExit>,
+) {
+	if keyboard_input.just_pressed(KeyCode::Y) {
+		app_exit_events.send(AppExit);
+	} else if keyboard_input.just_pressed(KeyCode::N) {
+		state.pop().unwrap();
+	}
+}
+
+fn cleanup_confirm_quit(mut commands: Commands, confirm_quit_data: Res<ConfirmQuitData>) {
+	commands
+		.entity(confirm_quit_data.root_entity)
+		.despawn_recursive();
+}
+
+// Sleep off the rest of the frame when a frame cap is set. Vsync is handled by the window itself.
+fn frame_limiter(display_settings: Res<DisplaySettings>, mut last_frame: Local<Option<Instant>>) {
+	if let (Some(cap), Some(last_frame)) = (display_settings.frame_cap, *last_frame) {
+		let frame_time = Duration::from_secs_f32(1.0 / cap as f32);
+		let elapsed = last_frame.elapsed();
+		if elapsed < frame_time {
+			thread::sleep(frame_time - elapsed);
+		}
+	}
+	*last_frame = Some(Instant::now());
 }
 
 fn change_color(
     time: Res<Time>,
+	game_rules: Res<GameRules>,
     mut assets: ResMut<Assets<ColorMaterial>>,
     query: Query<&Handle<ColorMaterial>, With<Sprite>>,
 ) {
+	if game_rules.reduced_motion {
+		return;
+	}
     for handle in query.iter() {
         let material = assets.get_mut(handle).unwrap();
         material
@@ -207,6 +750,210 @@ fn change_color(
     }
 }
 
+// Pulse food brightness so it is easy to spot. The color is derived from the food kind's color every
+// frame, so nothing accumulates and freshly spawned food is in sync from its first frame. Food must
+// use its own material so the player materials are never touched.
+fn pulse_food(
+	time: Res<Time>,
+	game_rules: Res<GameRules>,
+	mut assets: ResMut<Assets<ColorMaterial>>,
+	query: Query<(&Food, &Handle<ColorMaterial>)>,
+) {
+	// Food keeps the static color it spawned with
+	if game_rules.reduced_motion {
+		return;
+	}
+	let brightness = 0.75 + 0.25 * (time.seconds_since_startup() * 5.0).sin() as f32;
+	for (food, handle) in query.iter() {
+		let color = food.kind.color();
+		if let Some(material) = assets.get_mut(handle) {
+			material.color = Color::rgb(
+				(color.r() * brightness).clamp(0.0, 1.0),
+				(color.g() * brightness).clamp(0.0, 1.0),
+				(color.b() * brightness).clamp(0.0, 1.0),
+			);
+		}
+	}
+}
+
+// Keep `food_count` normal food on the board, and now and then add a bonus or shrink food if there
+// isn't one of that kind already
+fn food_spawner(
+	mut commands: Commands,
+	mut assets: ResMut<Assets<ColorMaterial>>,
+	game_rules: Res<GameRules>,
+	move_timer: Res<MoveTimer>,
+	food: Query<&Food>,
+	positions: Query<&Position>,
+) {
+	let count = |kind: FoodKind| food.iter().filter(|food| food.kind == kind).count();
+	let mut wanted = Vec::new();
+	for _ in count(FoodKind::Normal)..game_rules.food_count {
+		wanted.push(FoodKind::Normal);
+	}
+	if move_timer.0.just_finished() && random::<f32>() < SPECIAL_FOOD_CHANCE {
+		let kind = if random::<bool>() {
+			FoodKind::Bonus
+		} else {
+			FoodKind::Shrink
+		};
+		if count(kind) == 0 {
+			wanted.push(kind);
+		}
+	}
+	if wanted.is_empty() {
+		return;
+	}
+	let mut occupied = positions.iter().copied().collect::<HashSet<Position>>();
+	for kind in wanted {
+		if let Some(position) = free_position(&occupied) {
+			occupied.insert(position);
+			spawn_food(&mut commands, &mut assets, kind, position);
+		}
+	}
+}
+
+// Random cell that nothing occupies, if one turns up within a few tries
+fn free_position(occupied: &HashSet<Position>) -> Option<Position> {
+	(0..100)
+		.map(|_| Position {
+			x: (random::<f32>() * ARENA_WIDTH as f32) as i32,
+			y: (random::<f32>() * ARENA_HEIGHT as f32) as i32,
+		})
+		.find(|position| !occupied.contains(position))
+}
+
+fn spawn_food(
+	commands: &mut Commands,
+	assets: &mut Assets<ColorMaterial>,
+	kind: FoodKind,
+	position: Position,
+) {
+	let mut food = commands.spawn_bundle(SpriteBundle {
+		material: assets.add(kind.color().into()),
+		..Default::default()
+	});
+	food.insert(Food {
+		kind,
+		value: kind.value(),
+	})
+	.insert(position)
+	.insert(BoxSize::square(0.8));
+	if kind == FoodKind::Bonus {
+		food.insert(FoodLifetime(Timer::from_seconds(BONUS_FOOD_SECONDS, false)));
+	}
+}
+
+fn food_lifetime(
+	mut commands: Commands,
+	time: Res<Time>,
+	mut food: Query<(Entity, &mut FoodLifetime)>,
+) {
+	for (entity, mut lifetime) in food.iter_mut() {
+		if lifetime.0.tick(time.delta()).finished() {
+			commands.entity(entity).despawn();
+		}
+	}
+}
+
+// Minimap frame in the bottom right corner. Markers are added as its children by minimap_system.
+fn setup_minimap(mut commands: Commands, materials: Res<Materials>) {
+	commands
+		.spawn_bundle(NodeBundle {
+			style: Style {
+				size: Size::new(Val::Px(MINIMAP_SIZE), Val::Px(MINIMAP_SIZE)),
+				position_type: PositionType::Absolute,
+				position: Rect {
+					right: Val::Px(5.0),
+					bottom: Val::Px(5.0),
+					..Default::default()
+				},
+				..Default::default()
+			},
+			material: materials.minimap_material.clone(),
+			..Default::default()
+		})
+		.insert(Minimap);
+}
+
+// Redraw the minimap markers on every move. Markers use their own arena-to-minimap scaling rather than
+// position_translation. Long trails are sampled so the marker count stays small, and walls are left
+// out because they only line the border that the minimap frame already shows.
+fn minimap_system(
+	mut commands: Commands,
+	game_rules: Res<GameRules>,
+	materials: Res<Materials>,
+	move_timer: Res<MoveTimer>,
+	mut minimaps: Query<(Entity, &mut Visible), With<Minimap>>,
+	markers: Query<Entity, With<MinimapMarker>>,
+	heads: Query<&PlayerSegments, With<PlayerHead>>,
+	positions: Query<&Position>,
+	food: Query<(&Position, &Handle<ColorMaterial>), With<Food>>,
+) {
+	let (minimap, mut visible) = match minimaps.single_mut() {
+		Ok(minimap) => minimap,
+		Err(_) => return,
+	};
+	visible.is_visible = game_rules.show_minimap;
+	// Heads only move on a tick, so that is the only time the markers need rebuilding
+	if game_rules.show_minimap && !move_timer.0.just_finished() {
+		return;
+	}
+	for marker in markers.iter() {
+		commands.entity(marker).despawn_recursive();
+	}
+	if !game_rules.show_minimap {
+		return;
+	}
+	for segments in heads.iter() {
+		// Round up so a trail never gets more than MINIMAP_MAX_TRAIL_MARKERS markers
+		let stride = (segments.0.len() + MINIMAP_MAX_TRAIL_MARKERS - 1) / MINIMAP_MAX_TRAIL_MARKERS;
+		let stride = stride.max(1);
+		for (index, segment) in segments.0.iter().enumerate().step_by(stride) {
+			let material = if index == 0 {
+				&materials.head_material
+			} else {
+				&materials.segment_material
+			};
+			if let Ok(position) = positions.get(*segment) {
+				spawn_minimap_marker(&mut commands, minimap, position, material);
+			}
+		}
+	}
+	for (position, material) in food.iter() {
+		spawn_minimap_marker(&mut commands, minimap, position, material);
+	}
+}
+
+fn spawn_minimap_marker(
+	commands: &mut Commands,
+	minimap: Entity,
+	position: &Position,
+	material: &Handle<ColorMaterial>,
+) {
+	fn convert(pos: f32, bound_game: f32) -> f32 {
+		pos / bound_game * (MINIMAP_SIZE - MINIMAP_MARKER_SIZE)
+	}
+	commands.entity(minimap).with_children(|parent| {
+		parent
+			.spawn_bundle(NodeBundle {
+				style: Style {
+					size: Size::new(Val::Px(MINIMAP_MARKER_SIZE), Val::Px(MINIMAP_MARKER_SIZE)),
+					position_type: PositionType::Absolute,
+					position: Rect {
+						left: Val::Px(convert(position.x as f32, ARENA_WIDTH as f32)),
+						bottom: Val::Px(convert(position.y as f32, ARENA_HEIGHT as f32)),
+						..Default::default()
+					},
+					..Default::default()
+				},
+				material: material.clone(),
+				..Default::default()
+			})
+			.insert(MinimapMarker);
+	});
+}
+
 // This system updates the score for each entity with the "Player" and "Score" component.
 fn score_system(mut query: Query<(&Player, &mut Score)>) {
 	for (player, mut score) in query.iter_mut() {
@@ -238,34 +985,240 @@ fn size_scaling(windows: Res<Windows>, mut q: Query<(&BoxSize, &mut Sprite)>) {
 }
 
 fn position_translation(windows: Res<Windows>, mut q: Query<(&Position, &mut Transform)>) {
+    let window = windows.get_primary().unwrap();
+    for (pos, mut transform) in q.iter_mut() {
+        transform.translation = world_translation(window, pos);
+    }
+}
+
+// Center of an arena cell in world coordinates
+fn world_translation(window: &Window, pos: &Position) -> Vec3 {
     fn convert(pos: f32, bound_window: f32, bound_game: f32) -> f32 {
         let tile_size = bound_window / bound_game;
         pos / bound_game * bound_window - (bound_window / 2.) + (tile_size / 2.)
     }
-    let window = windows.get_primary().unwrap();
-    for (pos, mut transform) in q.iter_mut() {
-        transform.translation = Vec3::new(
-            convert(pos.x as f32, window.width() as f32, ARENA_WIDTH as f32),
-            convert(pos.y as f32, window.height() as f32, ARENA_HEIGHT as f32),
-            0.0,
-        );
-    }
+    Vec3::new(
+        convert(pos.x as f32, window.width() as f32, ARENA_WIDTH as f32),
+        convert(pos.y as f32, window.height() as f32, ARENA_HEIGHT as f32),
+        0.0,
+    )
+}
+
+// Float a "+N" (or "-N" for shrink food) over every eaten food cell, rising and fading out independently of other popups
+fn score_popup(
+	mut commands: Commands,
+	time: Res<Time>,
+	game_rules: Res<GameRules>,
+	windows: Res<Windows>,
+	asset_server: Res<AssetServer>,
+	mut growth_reader: EventReader<GrowthEvent>,
+	mut popups: Query<(Entity, &mut ScorePopup, &mut Transform, &mut Text)>,
+) {
+	for (entity, mut popup, mut transform, mut text) in popups.iter_mut() {
+		popup.timer.tick(time.delta());
+		if popup.timer.finished() {
+			commands.entity(entity).despawn();
+			continue;
+		}
+		// With reduced motion the popup just stays put until it despawns
+		if game_rules.reduced_motion {
+			continue;
+		}
+		let progress = popup.timer.percent();
+		transform.translation.y = popup.origin.y + SCORE_POPUP_RISE * progress;
+		text.sections[0].style.color.set_a(1.0 - progress);
+	}
+	let window = windows.get_primary().unwrap();
+	for event in growth_reader.iter() {
+		// Draw above the arena sprites
+		let origin = world_translation(window, &event.position) + Vec3::Z;
+		commands
+			.spawn_bundle(Text2dBundle {
+				text: Text::with_section(
+					format!("{:+}", event.value),
+					TextStyle {
+						font: asset_server.load("fonts/Chivo-Regular.ttf"),
+						font_size: 16.0,
+						color: Color::rgb(0.9, 0.9, 0.9),
+					},
+					TextAlignment {
+						vertical: VerticalAlign::Center,
+						horizontal: HorizontalAlign::Center,
+					},
+				),
+				transform: Transform::from_translation(origin),
+				..Default::default()
+			})
+			.insert(ScorePopup {
+				timer: Timer::from_seconds(SCORE_POPUP_SECONDS, false),
+				origin,
+			});
+	}
 }
 
-// This system runs on all entities with the "Player" and "Score" components, but it also
+// This system runs on all heads with a "PlayerName" and "Score" component, but it also
 // accesses the "GameRules" resource to determine if a player has won.
 fn score_check_system(
 	game_rules: Res<GameRules>,
 	mut game_state: ResMut<GameState>,
-	query: Query<(&Player, &Score)>,
+	query: Query<(&PlayerName, &Score)>,
+) {
+	for (name, score) in query.iter() {
+		if score.value >= game_rules.winning_score {
+			game_state.winning_player = Some(name.0.clone());
+		}
+	}
+}
+
+// Once someone has won, freeze the game under the victory sequence
+fn start_victory(game_state: Res<GameState>, mut state: ResMut<State<AppState>>) {
+	if game_state.winning_player.is_some() {
+		if let Err(error) = state.push(AppState::Victory) {
+			println!("Victory skipped: {:?}", error);
+		}
+	}
+}
+
+// Give the winner's trail segments their own materials so cycling their colors leaves everyone
+// else alone
+fn setup_victory(
+	mut commands: Commands,
+	mut assets: ResMut<Assets<ColorMaterial>>,
+	materials: Res<Materials>,
+	game_state: Res<GameState>,
+	heads: Query<(&PlayerName, &PlayerColor, &PlayerSegments)>,
+	mut segment_materials: Query<&mut Handle<ColorMaterial>, With<PlayerSegment>>,
+) {
+	commands.insert_resource(VictorySequence(Timer::from_seconds(VICTORY_SECONDS, false)));
+	if let Some(ref winner) = game_state.winning_player {
+		println!("{} won the game!", winner);
+		for (_, color, segments) in heads.iter().filter(|(name, _, _)| &name.0 == winner) {
+			for segment in segments.0.iter().skip(1) {
+				if let Ok(mut handle) = segment_materials.get_mut(*segment) {
+					if *handle == materials.segment_material {
+						*handle = assets.add(color.0.into());
+					}
+				}
+			}
+		}
+	}
+}
+
+// Cycle the winner's trail through the colors, then move on to the game over screen. With reduced
+// motion the trail stays as it is and only the delay remains.
+fn victory_sequence(
+	time: Res<Time>,
+	game_rules: Res<GameRules>,
+	game_state: Res<GameState>,
+	mut victory: ResMut<VictorySequence>,
+	mut state: ResMut<State<AppState>>,
+	mut assets: ResMut<Assets<ColorMaterial>>,
+	heads: Query<(&PlayerName, &PlayerSegments)>,
+	segment_materials: Query<&Handle<ColorMaterial>, With<PlayerSegment>>,
 ) {
-	for (player, score) in query.iter() {
-		if score.value == game_rules.winning_score {
-			game_state.winning_player = Some(player.name.clone());
+	if victory.0.tick(time.delta()).just_finished() {
+		state.replace(AppState::GameOver).unwrap();
+		return;
+	}
+	let winner = match game_state.winning_player {
+		Some(ref winner) if !game_rules.reduced_motion => winner,
+		_ => return,
+	};
+	let phase = time.seconds_since_startup() as f32 * 10.0;
+	for (_, segments) in heads.iter().filter(|(name, _)| &name.0 == winner) {
+		for (index, segment) in segments.0.iter().enumerate().skip(1) {
+			let t = phase - index as f32 * 0.3;
+			let color = Color::rgb(
+				0.5 + 0.5 * t.sin(),
+				0.5 + 0.5 * (t + 2.1).sin(),
+				0.5 + 0.5 * (t + 4.2).sin(),
+			);
+			if let Ok(handle) = segment_materials.get(*segment) {
+				if let Some(material) = assets.get_mut(handle) {
+					material.color = color;
+				}
+			}
 		}
 	}
 }
 
+fn setup_game_over(
+	mut commands: Commands,
+	asset_server: Res<AssetServer>,
+	mut materials: ResMut<Assets<ColorMaterial>>,
+	game_state: Res<GameState>,
+) {
+	let message = match game_state.winning_player {
+		Some(ref winner) => format!("{} won the game!", winner),
+		None => "Game over".to_string(),
+	};
+	let font: Handle<Font> = asset_server.load("fonts/Chivo-Regular.ttf");
+	let root_entity = commands
+		.spawn_bundle(NodeBundle {
+			style: Style {
+				size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+				flex_direction: FlexDirection::ColumnReverse,
+				justify_content: JustifyContent::Center,
+				align_items: AlignItems::Center,
+				..Default::default()
+			},
+			material: materials.add(Color::NONE.into()),
+			..Default::default()
+		})
+		.with_children(|parent| {
+			let lines = [(message.as_str(), 40.0), ("Press Enter for the menu", 20.0)];
+			for (line, font_size) in lines.iter() {
+				parent.spawn_bundle(TextBundle {
+					text: Text::with_section(
+						*line,
+						TextStyle {
+							font: font.clone(),
+							font_size: *font_size,
+							color: Color::rgb(0.9, 0.9, 0.9),
+						},
+						Default::default(),
+					),
+					..Default::default()
+				});
+			}
+		})
+		.id();
+	commands.insert_resource(GameOverData { root_entity });
+}
+
+fn game_over_menu(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<AppState>>) {
+	if keyboard_input.just_pressed(KeyCode::Return) {
+		state.set(AppState::MainMenu).unwrap();
+	}
+}
+
+// Forget the finished game so the next one starts without a winner
+fn cleanup_game_over(
+	mut commands: Commands,
+	game_over_data: Res<GameOverData>,
+	mut game_state: ResMut<GameState>,
+	mut summary: ResMut<RoundSummary>,
+) {
+	commands.entity(game_over_data.root_entity).despawn_recursive();
+	game_state.winning_player = None;
+	*summary = RoundSummary::default();
+}
+
+// Clear the board once a game is over, so nothing of it is left under the game over screen or in
+// the next game
+fn cleanup_game(
+	mut commands: Commands,
+	board: Query<Entity, Or<(With<Position>, With<ScorePopup>, With<Player>)>>,
+	minimaps: Query<Entity, With<Minimap>>,
+) {
+	for entity in board.iter() {
+		commands.entity(entity).despawn();
+	}
+	for minimap in minimaps.iter() {
+		commands.entity(minimap).despawn_recursive();
+	}
+}
+
 // This system ends the game if we meet the right conditions. This fires an AppExit event, which
 // tells our App to quit. Check out the "event.rs" example if you want to learn more about using
 // events.
@@ -292,24 +1245,18 @@ fn startup_system(
 	mut game_state: ResMut<GameState>,
 	mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-	// Create our game rules resource
-	commands.insert_resource(GameRules {
-		max_rounds: 100,
-		winning_score: 51,
-		max_players: 4,
-	});
 	commands.spawn_batch(vec![
 		(
 			Player {
 				name: "Quorra".to_string(),
-				head: PlayerHead {direction: Direction::Up},
+				head: PlayerHead::new(Direction::Up),
 			},
 			Score { value: 0 },
 		),
 		(
 			Player {
 				name: "Clu".to_string(),
-				head: PlayerHead {direction: Direction::Down},
+				head: PlayerHead::new(Direction::Down),
 			},
 			Score { value: 0 },
 		),
@@ -337,81 +1284,273 @@ fn new_player_system(
 	let add_new_player = random::<bool>();
 	if add_new_player && game_state.total_players < game_rules.max_players {
 		game_state.total_players += 1;
+		let name = format!("Player {}", game_state.total_players);
 		commands.spawn_bundle((
 			Player {
-				name: format!("Player {}", game_state.total_players),
-				head: PlayerHead {direction: Direction::Down},
+				name: name.clone(),
+				head: PlayerHead::new(Direction::Down),
 			},
 			Score { value: 0 },
 		));
 
-		println!("Player {} joined the game!", game_state.total_players);
+		println!("{} joined the game!", name);
+	}
+}
+
+// Show a short-lived line of text for game events. Live notifications are stacked from the top of
+// the screen, oldest first, so new ones never overwrite the ones still showing.
+fn notification_system(
+	mut commands: Commands,
+	time: Res<Time>,
+	asset_server: Res<AssetServer>,
+	mut joined_reader: EventReader<PlayerJoinedEvent>,
+	mut notifications: Query<(Entity, &mut Notification, &mut Style)>,
+) {
+	let mut live = Vec::new();
+	for (entity, mut notification, style) in notifications.iter_mut() {
+		notification.0.tick(time.delta());
+		if notification.0.finished() {
+			commands.entity(entity).despawn();
+		} else {
+			live.push((notification.0.elapsed(), style));
+		}
+	}
+	live.sort_by(|a, b| b.0.cmp(&a.0));
+	let mut slot = live.len();
+	for (index, (_, mut style)) in live.into_iter().enumerate() {
+		style.position.top = Val::Px(index as f32 * NOTIFICATION_SPACING);
+	}
+	for event in joined_reader.iter() {
+		spawn_notification(
+			&mut commands,
+			&asset_server,
+			format!("{} joined the game!", event.name),
+			slot,
+		);
+		slot += 1;
+	}
+}
+
+// Announce the human player once per game, not on every respawn
+fn announce_player(mut joined_writer: EventWriter<PlayerJoinedEvent>) {
+	joined_writer.send(PlayerJoinedEvent {
+		name: PLAYER_NAME.to_string(),
+	});
+}
+
+fn cleanup_notifications(mut commands: Commands, notifications: Query<Entity, With<Notification>>) {
+	for entity in notifications.iter() {
+		commands.entity(entity).despawn();
 	}
 }
 
+fn spawn_notification(
+	commands: &mut Commands,
+	asset_server: &AssetServer,
+	message: String,
+	slot: usize,
+) {
+	commands
+		.spawn_bundle(TextBundle {
+			style: Style {
+				position_type: PositionType::Absolute,
+				position: Rect {
+					top: Val::Px(slot as f32 * NOTIFICATION_SPACING),
+					left: Val::Px(5.0),
+					..Default::default()
+				},
+				..Default::default()
+			},
+			text: Text::with_section(
+				message,
+				TextStyle {
+					font: asset_server.load("fonts/Chivo-Regular.ttf"),
+					font_size: 16.0,
+					color: Color::rgb(0.9, 0.9, 0.9),
+				},
+				Default::default(),
+			),
+			..Default::default()
+		})
+		.insert(Notification(Timer::from_seconds(NOTIFICATION_SECONDS, false)));
+}
+
 // Spawn new tron player
 fn spawn_player(
-    mut commands: Commands,
-    materials: Res<Materials>,
-    mut segments: ResMut<PlayerSegments>,
+	mut commands: Commands,
+	materials: Res<Materials>,
+	game_rules: Res<GameRules>,
+	mut grace: ResMut<RespawnGrace>,
 ) {
 	println!("\n\nSPAWN\n\n");
-    segments.0 = vec![
-        commands
-            .spawn_bundle(SpriteBundle {
-                material: materials.head_material.clone(),
-                sprite: Sprite::new(Vec2::new(10.0, 10.0)),
-                ..Default::default()
-            })
-            .insert(PlayerHead {
-                direction: Direction::Up,
-            })
-            .insert(PlayerSegment)
-            .insert(Position { x: 3, y: 3 })
-            .insert(BoxSize::square(0.8))
-            .id(),
-        spawn_segment(
-            commands,
-            &materials.segment_material,
-            Position { x: 3, y: 2 },
-        ),
-    ];
+	*grace = RespawnGrace::new(game_rules.respawn_grace);
+	spawn_head(
+		&mut commands,
+		&materials,
+		PLAYER_NAME.to_string(),
+		Color::rgb(0.1, 0.7, 0.7),
+		Position { x: 3, y: 3 },
+		Position { x: 3, y: 2 },
+		Direction::Up,
+	);
+}
+
+// Spawn a head with one tail segment. The head owns its own PlayerSegments list.
+fn spawn_head(
+	commands: &mut Commands,
+	materials: &Materials,
+	name: String,
+	color: Color,
+	position: Position,
+	tail_position: Position,
+	direction: Direction,
+) -> Entity {
+	let head = commands
+		.spawn_bundle(SpriteBundle {
+			material: materials.head_material.clone(),
+			sprite: Sprite::new(Vec2::new(10.0, 10.0)),
+			..Default::default()
+		})
+		.insert(PlayerHead::new(direction))
+		.insert(PlayerName(name))
+		.insert(PlayerColor(color))
+		.insert(Score { value: 0 })
+		.insert(LastTailPosition::default())
+		.insert(PlayerSegment)
+		.insert(position)
+		.insert(BoxSize::square(0.8))
+		.id();
+	let tail = spawn_segment(commands, &materials.segment_material, tail_position);
+	commands.entity(head).insert(PlayerSegments(vec![head, tail]));
+	head
+}
+
+// Spawn the arena border. Walls sit on the outermost cells so they render at the window edges.
+fn spawn_walls(mut commands: Commands, materials: Res<Materials>, game_rules: Res<GameRules>) {
+	if game_rules.wrap_around {
+		return;
+	}
+	let width = ARENA_WIDTH as i32;
+	let height = ARENA_HEIGHT as i32;
+	let horizontal = (0..width).flat_map(|x| vec![Position { x, y: 0 }, Position { x, y: height - 1 }]);
+	let vertical = (1..height - 1).flat_map(|y| vec![Position { x: 0, y }, Position { x: width - 1, y }]);
+	for position in horizontal.chain(vertical) {
+		commands
+			.spawn_bundle(SpriteBundle {
+				material: materials.wall_material.clone(),
+				..Default::default()
+			})
+			.insert(Wall)
+			.insert(position)
+			.insert(BoxSize::square(1.0));
+	}
+}
+
+fn setup_move_timer(game_rules: Res<GameRules>, mut move_timer: ResMut<MoveTimer>) {
+	move_timer.0 = Timer::from_seconds(game_rules.move_interval, true);
+}
+
+// Advance the move timer. Holding Tab fast-forwards the simulation for debugging. Only the elapsed
+// time fed to the timer is scaled, so releasing the key leaves the timer mid-interval as usual.
+fn move_timer_tick(
+	time: Res<Time>,
+	keyboard_input: Res<Input<KeyCode>>,
+	mut move_timer: ResMut<MoveTimer>,
+) {
+	let rate = if keyboard_input.pressed(KeyCode::Tab) {
+		FAST_FORWARD_RATE
+	} else {
+		1.0
+	};
+	// Movement and growth run once per frame, so never feed the timer more than one interval.
+	// Fast-forward therefore tops out at one move per frame when the frame rate is low.
+	let delta = time.delta().mul_f32(rate).min(move_timer.0.duration());
+	move_timer.0.tick(delta);
+}
+
+// Reversals are checked against the direction of the last move, not the pending one, so two quick
+// turns between ticks can't fold the head back onto its own trail.
+fn accept_turn(head: &PlayerHead, dir: Direction) -> bool {
+	dir != head.committed_direction.opposite()
 }
 
 // Move player
-fn player_movement_input(keyboard_input: Res<Input<KeyCode>>, mut heads: Query<&mut PlayerHead>, state: ResMut<State<AppState>>,) {
+fn player_movement_input(
+	time: Res<Time>,
+	keyboard_input: Res<Input<KeyCode>>,
+	mut grace: ResMut<RespawnGrace>,
+	mut heads: Query<&mut PlayerHead>,
+	state: ResMut<State<AppState>>,
+) {
+	grace.timer.tick(time.delta());
+	// After a respawn only fresh presses count, and the first one ends the grace
+	let fresh_only = grace.needs_fresh_press;
+	let pressed = |key: KeyCode| {
+		if fresh_only {
+			keyboard_input.just_pressed(key)
+		} else {
+			keyboard_input.pressed(key)
+		}
+	};
     if let Some(mut head) = heads.iter_mut().next() {
-        let dir: Direction = if keyboard_input.pressed(KeyCode::Left) {
-            Direction::Left
-        } else if keyboard_input.pressed(KeyCode::Down) {
-            Direction::Down
-        } else if keyboard_input.pressed(KeyCode::Up) {
-            Direction::Up
-        } else if keyboard_input.pressed(KeyCode::Right) {
-            Direction::Right
+        let dir: Option<Direction> = if pressed(KeyCode::Left) {
+            Some(Direction::Left)
+        } else if pressed(KeyCode::Down) {
+            Some(Direction::Down)
+        } else if pressed(KeyCode::Up) {
+            Some(Direction::Up)
+        } else if pressed(KeyCode::Right) {
+            Some(Direction::Right)
         } else {
-            head.direction
+            None
         };
-        if dir != head.direction.opposite() {
-            head.direction = dir;
+        if let Some(dir) = dir {
+			// A rejected reversal doesn't count as the fresh press that ends the grace
+			if accept_turn(&head, dir) {
+				head.direction = dir;
+				if fresh_only {
+					grace.end();
+				}
+			}
         }
     }
 	println!("{:?}", state.current());
 }
 
 fn player_movement(
-    segments: ResMut<PlayerSegments>,
-    mut heads: Query<(Entity, &PlayerHead)>,
-    mut positions: Query<&mut Position>,
-	mut game_over_writer: EventWriter<GameOverEvent>,
+	mut commands: Commands,
+	game_rules: Res<GameRules>,
+	move_timer: Res<MoveTimer>,
+	grace: Res<RespawnGrace>,
+	mut tick: ResMut<TickCounter>,
+	mut heads: Query<
+		(Entity, &mut PlayerHead, &PlayerSegments, &mut LastTailPosition),
+		Without<Eliminated>,
+	>,
+	trails: Query<&PlayerSegments>,
+    mut positions: Query<&mut Position, Without<Wall>>,
+	walls: Query<&Position, With<Wall>>,
 ) {
-    if let Some((head_entity, head)) = heads.iter_mut().next() {
+	if !move_timer.0.just_finished() || grace.active() {
+		return;
+	}
+	tick.0 += 1;
+	// Every trail cell before anyone moves, so heads also collide with other players' trails
+	let occupied = trails
+		.iter()
+		.flat_map(|segments| segments.0.iter())
+		.map(|e| *positions.get_mut(*e).unwrap())
+		.collect::<Vec<Position>>();
+	let mut moved_heads = Vec::new();
+    for (head_entity, mut head, segments, mut last_tail) in heads.iter_mut() {
         let segment_positions = segments
             .0
             .iter()
             .map(|e| *positions.get_mut(*e).unwrap())
             .collect::<Vec<Position>>();
+		last_tail.0 = segment_positions.last().copied();
         let mut head_pos = positions.get_mut(head_entity).unwrap();
+		head.committed_direction = head.direction;
         match &head.direction {
             Direction::Left => {
                 head_pos.x -= 1;
@@ -426,16 +1565,11 @@ fn player_movement(
                 head_pos.y -= 1;
             }
         };
-		if segment_positions.contains(&head_pos) {
-			game_over_writer.send(GameOverEvent);
-		}
-		if head_pos.x < 0
-			|| head_pos.y < 0
-			|| head_pos.x as u32 >= ARENA_WIDTH
-			|| head_pos.y as u32 >= ARENA_HEIGHT
-		{
-			game_over_writer.send(GameOverEvent);
+		if game_rules.wrap_around {
+			head_pos.x = head_pos.x.rem_euclid(ARENA_WIDTH as i32);
+			head_pos.y = head_pos.y.rem_euclid(ARENA_HEIGHT as i32);
 		}
+		moved_heads.push((head_entity, *head_pos));
         segment_positions
             .iter()
             .zip(segments.0.iter().skip(1))
@@ -443,24 +1577,201 @@ fn player_movement(
                 *positions.get_mut(*segment).unwrap() = *pos;
             });
     }
+	// Heads moving into the same cell crash together and share the death tick
+	for (head_entity, head_pos) in moved_heads.iter() {
+		let head_on = moved_heads.iter().filter(|(_, other)| other == head_pos).count() > 1;
+		if head_on || occupied.contains(head_pos) || walls.iter().any(|wall| wall == head_pos) {
+			commands
+				.entity(*head_entity)
+				.insert(Eliminated { death_tick: tick.0 });
+		}
+	}
+}
+
+// End the round once at most one head is left, or none in a single player game. Each player gets a
+// point for every opponent that crashed strictly before them, so the last survivor scores the most
+// and heads that crashed on the same tick tie. A last survivor wins the game.
+fn round_end_check(
+	mut game_state: ResMut<GameState>,
+	mut summary: ResMut<RoundSummary>,
+	heads: Query<(&PlayerName, Option<&Eliminated>), With<PlayerHead>>,
+	mut game_over_writer: EventWriter<GameOverEvent>,
+) {
+	let total = heads.iter().count();
+	let alive = heads.iter().filter(|(_, eliminated)| eliminated.is_none()).count();
+	let survivors = if total > 1 { 1 } else { 0 };
+	if total == 0 || alive > survivors {
+		return;
+	}
+	fn outlived(death_tick: Option<usize>, other: Option<usize>) -> bool {
+		match (death_tick, other) {
+			(_, None) => false,
+			(None, Some(_)) => true,
+			(Some(own), Some(other)) => other < own,
+		}
+	}
+	let death_ticks = heads
+		.iter()
+		.map(|(name, eliminated)| (name.0.clone(), eliminated.map(|e| e.death_tick)))
+		.collect::<Vec<(String, Option<usize>)>>();
+	summary.standings = death_ticks
+		.iter()
+		.map(|(name, death_tick)| Standing {
+			name: name.clone(),
+			death_tick: *death_tick,
+			points: death_ticks
+				.iter()
+				.filter(|(_, other)| outlived(*death_tick, *other))
+				.count(),
+		})
+		.collect();
+	summary.standings.sort_by(|a, b| b.points.cmp(&a.points));
+	for (place, standing) in summary.standings.iter().enumerate() {
+		println!(
+			"{}. {} ({} points, died on tick {:?})",
+			place + 1,
+			standing.name,
+			standing.points,
+			standing.death_tick
+		);
+	}
+	// A last survivor wins outright, otherwise everyone respawns for another round
+	match summary.standings.iter().find(|standing| standing.death_tick.is_none()) {
+		Some(survivor) => game_state.winning_player = Some(survivor.name.clone()),
+		None => game_over_writer.send(GameOverEvent),
+	}
+}
+
+// Heads entering a food cell eat it. Positive values are scored and add that many segments at the
+// tail, shrink food removes tail segments down to MIN_TRAIL_LENGTH. Sprint mode keeps the length.
+fn eating(
+	mut commands: Commands,
+	materials: Res<Materials>,
+	game_rules: Res<GameRules>,
+	mut growth_writer: EventWriter<GrowthEvent>,
+	food: Query<(Entity, &Position, &Food)>,
+	mut heads: Query<
+		(&Position, &LastTailPosition, &mut Score, &mut PlayerSegments),
+		(With<PlayerHead>, Without<Eliminated>),
+	>,
+) {
+	let mut eaten = Vec::new();
+	for (head_pos, last_tail, mut score, mut segments) in heads.iter_mut() {
+		for (food_entity, food_pos, food) in food.iter() {
+			if food_pos != head_pos || eaten.contains(&food_entity) {
+				continue;
+			}
+			eaten.push(food_entity);
+			commands.entity(food_entity).despawn();
+			score.value += food.value.max(0) as usize;
+			growth_writer.send(GrowthEvent {
+				position: *food_pos,
+				value: food.value,
+			});
+			if game_rules.mode == GameMode::Sprint {
+				continue;
+			}
+			if let Some(tail) = last_tail.0 {
+				for _ in 0..food.value {
+					segments.0.push(spawn_segment(
+						&mut commands,
+						&materials.segment_material,
+						tail,
+					));
+				}
+			}
+			for _ in food.value..0 {
+				if segments.0.len() <= MIN_TRAIL_LENGTH {
+					break;
+				}
+				if let Some(segment) = segments.0.pop() {
+					commands.entity(segment).despawn();
+				}
+			}
+		}
+	}
+}
+
+// In sprint mode every bite makes the game a little faster
+fn sprint_speedup(
+	game_rules: Res<GameRules>,
+	mut growth_reader: EventReader<GrowthEvent>,
+	mut move_timer: ResMut<MoveTimer>,
+) {
+	if game_rules.mode != GameMode::Sprint {
+		return;
+	}
+	for _ in growth_reader.iter() {
+		let interval = move_timer.0.duration().as_secs_f32() * SPRINT_SPEEDUP;
+		move_timer
+			.0
+			.set_duration(Duration::from_secs_f32(interval.max(SPRINT_MIN_INTERVAL)));
+	}
 }
 
 fn player_growth(
-    commands: Commands,
-    head_positions: Query<&Position, With<PlayerHead>>,
-    mut segments: ResMut<PlayerSegments>,
+    mut commands: Commands,
+    mut heads: Query<
+		(&LastTailPosition, &mut PlayerSegments),
+		(With<PlayerHead>, Without<Eliminated>),
+	>,
     materials: Res<Materials>,
+	game_rules: Res<GameRules>,
+	move_timer: Res<MoveTimer>,
+	grace: Res<RespawnGrace>,
 ) {
+	if !move_timer.0.just_finished() || grace.active() || game_rules.mode == GameMode::Sprint {
+		return;
+	}
 	println!("\n\nIN GROWTH\n\n");
-	segments.0.push(spawn_segment( // This would add the tail always to the same player
-		commands,
-		&materials.segment_material,
-		head_positions.single().unwrap().clone().into(),
-	));
+	for (last_tail, mut segments) in heads.iter_mut() {
+		if let Some(position) = last_tail.0 {
+			segments.0.push(spawn_segment(
+				&mut commands,
+				&materials.segment_material,
+				position,
+			));
+		}
+	}
+}
+
+// Shade each trail from the player's own color at the neck, fading toward the tail. Segments get
+// their own material the first time they are shaded. Runs only when a trail changed or a segment
+// spawned, since new segment entities show up a frame after their trail grew.
+fn trail_gradient(
+	mut assets: ResMut<Assets<ColorMaterial>>,
+	materials: Res<Materials>,
+	heads: Query<(&PlayerSegments, &PlayerColor)>,
+	changed_heads: Query<Entity, (With<PlayerHead>, Changed<PlayerSegments>)>,
+	new_segments: Query<Entity, Added<PlayerSegment>>,
+	mut segment_materials: Query<&mut Handle<ColorMaterial>, With<PlayerSegment>>,
+) {
+	if changed_heads.iter().next().is_none() && new_segments.iter().next().is_none() {
+		return;
+	}
+	for (segments, color) in heads.iter() {
+		let trail_length = segments.0.len().saturating_sub(2).max(1) as f32;
+		// The first entry is the head, which keeps its own material
+		for (index, segment) in segments.0.iter().enumerate().skip(1) {
+			let brightness = 1.0 - (1.0 - TRAIL_MIN_BRIGHTNESS) * (index - 1) as f32 / trail_length;
+			let shade = Color::rgb(
+				color.0.r() * brightness,
+				color.0.g() * brightness,
+				color.0.b() * brightness,
+			);
+			if let Ok(mut handle) = segment_materials.get_mut(*segment) {
+				if *handle == materials.segment_material {
+					*handle = assets.add(shade.into());
+				} else if let Some(material) = assets.get_mut(&*handle) {
+					material.color = shade;
+				}
+			}
+		}
+	}
 }
 
 fn spawn_segment(
-    mut commands: Commands,
+    commands: &mut Commands,
     material: &Handle<ColorMaterial>,
     position: Position,
 ) -> Entity {
@@ -479,8 +1790,10 @@ fn game_over(
     mut commands: Commands,
     mut reader: EventReader<GameOverEvent>,
     materials: Res<Materials>,
-	players: Query<Entity, With<Position>>,
-    segments_res: ResMut<PlayerSegments>,
+	game_rules: Res<GameRules>,
+	grace: ResMut<RespawnGrace>,
+	mut move_timer: ResMut<MoveTimer>,
+	players: Query<Entity, (With<Position>, Without<Wall>)>,
     segments: Query<Entity, With<PlayerSegment>>,
 ) {
     if reader.iter().next().is_some() {
@@ -488,7 +1801,9 @@ fn game_over(
 		for ent in players.iter().chain(segments.iter()) {
             commands.entity(ent).despawn();
         }
-        spawn_player(commands, materials, segments_res); // Before this line delete the player trail
+		// Undo any sprint speed-up from the last round
+		move_timer.0 = Timer::from_seconds(game_rules.move_interval, true);
+        spawn_player(commands, materials, game_rules, grace); // Before this line delete the player trail
     }
 }
 
@@ -501,28 +1816,37 @@ enum MyStage {
 #[derive(Debug, Hash, PartialEq, Eq, Clone, SystemLabel)]
 enum MyLabels {
 	ScoreCheck,
+	RoundEnd,
+	Victory,
 }
 
 // Our Bevy app's entry point
 fn main() {
 	// Bevy apps are created using the builder pattern. We use the builder to add systems,
 	// resources, and plugins to our app
+	// Window settings have to be known before DefaultPlugins creates the window
+	let display_settings = DisplaySettings::load();
 	App::build()
 		.insert_resource(ReportExecutionOrderAmbiguities)
-		.add_plugins(DefaultPlugins)
 		// Resize and rename window
 		.insert_resource(WindowDescriptor { // <--
 			title: "Nuisance Value".to_string(), // <--
 			width: 500.0,                 // <--
 			height: 500.0,                // <--
+			vsync: display_settings.vsync,
 			..Default::default()         // <--
 		})
+		.insert_resource(display_settings)
+		.add_plugins(DefaultPlugins)
+		.add_system_to_stage(CoreStage::Last, frame_limiter.system())
 		.add_state(AppState::MainMenu)
 		// Change colors
 		.insert_resource(ClearColor(Color::rgb(0.04, 0.04, 0.04)))
-		// Player tails
-		.insert_resource(PlayerSegments::default())
-		.insert_resource(LastTailPosition::default())
+		// Movement pacing and round bookkeeping
+		.init_resource::<MoveTimer>()
+		.init_resource::<TickCounter>()
+		.init_resource::<RespawnGrace>()
+		.init_resource::<RoundSummary>()
 		// Some systems are configured by adding their settings as a resource
 		//.insert_resource(ScheduleRunnerSettings::run_loop(Duration::from_secs(5)))
 		// Plugins are just a grouped set of app builder calls (just like we're doing here).
@@ -532,13 +1856,17 @@ fn main() {
 		//.add_plugin(ScheduleRunnerPlugin::default())
 		// Resources that implement the Default or FromResources trait can be added like this:
 		.init_resource::<GameState>()
+		.init_resource::<GameRules>()
 		// Startup systems run exactly once BEFORE all other systems. These are generally used for
 		// app initialization code (ex: adding entities and resources)
 		//.add_startup_system(startup_system.system())
+		.add_startup_system(setup_cameras.system())
 		// Add Player death
 		.add_event::<GameOverEvent>()
 		// Add tail event
 		.add_event::<GrowthEvent>()
+		// On-screen notifications
+		.add_event::<PlayerJoinedEvent>()
 		// Add game setup to stage
 		//.add_startup_stage("game_setup", SystemStage::single(spawn_player.system()))
 		// SYSTEM EXECUTION ORDER
@@ -633,6 +1961,10 @@ fn main() {
 					.label(PlayerMovement::Spawn)
 					//.before(PlayerMovement::Movement)
 				)
+				.with_system(spawn_walls.system())
+				.with_system(setup_minimap.system())
+				.with_system(setup_move_timer.system())
+				.with_system(announce_player.system())
 		)
         .add_system_set(
             SystemSet::on_update(AppState::InGame)
@@ -650,18 +1982,323 @@ fn main() {
 					.label(PlayerMovement::Input)
 					.before(PlayerMovement::Movement),
 				)
+				.with_system(
+					move_timer_tick
+					.system()
+					.label(PlayerMovement::Tick)
+					.before(PlayerMovement::Movement),
+				)
 				.with_system(
 					player_movement.system()
 					.label(PlayerMovement::Movement)
 					.after(PlayerMovement::Spawn)
+					.after(PlayerMovement::Tick)
+				)
+				.with_system(
+					eating
+					.system()
+					.after(PlayerMovement::Movement)
+				)
+				.with_system(score_popup.system())
+				.with_system(
+					food_spawner
+					.system()
+					.after(PlayerMovement::Tick)
+				)
+				.with_system(food_lifetime.system())
+				.with_system(
+					trail_gradient
+					.system()
+					.after(PlayerMovement::Growth)
+				)
+				.with_system(sprint_speedup.system())
+				.with_system(
+					round_end_check
+					.system()
+					.label(MyLabels::RoundEnd)
+					.before(PlayerMovement::Movement)
 				)
 				.with_system(
 					game_over
 					.system()
+					.after(MyLabels::RoundEnd)
 					.before(PlayerMovement::Movement)
 				)
 				.with_system(position_translation.system())
 				.with_system(size_scaling.system())
+				.with_system(pulse_food.system())
+				.with_system(notification_system.system())
+				.with_system(
+					minimap_system
+					.system()
+					.after(PlayerMovement::Movement)
+				)
+				.with_system(
+					request_quit
+					.system()
+					.after(MyLabels::Victory)
+				)
+				.with_system(
+					score_check_system
+					.system()
+					.label(MyLabels::ScoreCheck)
+				)
+				.with_system(
+					start_victory
+					.system()
+					.label(MyLabels::Victory)
+					.after(MyLabels::ScoreCheck)
+					.after(MyLabels::RoundEnd)
+				)
         )
+		.add_system_set(
+			SystemSet::on_enter(AppState::Victory)
+				.with_system(setup_victory.system())
+		)
+		.add_system_set(
+			SystemSet::on_update(AppState::Victory)
+				.with_system(victory_sequence.system())
+		)
+		.add_system_set(
+			SystemSet::on_exit(AppState::InGame)
+				.with_system(cleanup_notifications.system())
+				.with_system(cleanup_game.system())
+		)
+		.add_system_set(
+			SystemSet::on_enter(AppState::GameOver)
+				.with_system(setup_game_over.system())
+		)
+		.add_system_set(
+			SystemSet::on_update(AppState::GameOver)
+				.with_system(game_over_menu.system())
+		)
+		.add_system_set(
+			SystemSet::on_exit(AppState::GameOver)
+				.with_system(cleanup_game_over.system())
+		)
+		.add_system_set(
+			SystemSet::on_enter(AppState::ConfirmQuit)
+				.with_system(setup_confirm_quit.system())
+		)
+		.add_system_set(
+			SystemSet::on_update(AppState::ConfirmQuit)
+				.with_system(confirm_quit.system())
+		)
+		.add_system_set(
+			SystemSet::on_exit(AppState::ConfirmQuit)
+				.with_system(cleanup_confirm_quit.system())
+		)
 		.run();
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration;
+
+	fn spawn_two_heads(mut commands: Commands, materials: Res<Materials>) {
+		spawn_head(
+			&mut commands,
+			&materials,
+			"Player 1".to_string(),
+			Color::rgb(0.1, 0.7, 0.7),
+			Position { x: 3, y: 3 },
+			Position { x: 3, y: 2 },
+			Direction::Up,
+		);
+		spawn_head(
+			&mut commands,
+			&materials,
+			"Player 2".to_string(),
+			Color::rgb(0.7, 0.1, 0.7),
+			Position { x: 10, y: 10 },
+			Position { x: 10, y: 9 },
+			Direction::Up,
+		);
+	}
+
+	// Resources shared by the movement systems. Placeholder materials are enough without a renderer.
+	fn test_world() -> World {
+		let mut world = World::default();
+		world.insert_resource(Materials {
+			head_material: Handle::default(),
+			segment_material: Handle::default(),
+			wall_material: Handle::default(),
+			minimap_material: Handle::default(),
+		});
+		world.insert_resource(GameRules::default());
+		world.insert_resource(TickCounter::default());
+		world.insert_resource(MoveTimer::default());
+		let mut grace = RespawnGrace::new(0.1);
+		grace.end();
+		world.insert_resource(grace);
+		world
+	}
+
+	// Run one full interval through the move timer, so the next movement system call moves
+	fn finish_move_timer(world: &mut World) {
+		let mut move_timer = world.get_resource_mut::<MoveTimer>().unwrap();
+		let interval = move_timer.0.duration();
+		move_timer.0.tick(interval);
+	}
+
+	#[test]
+	fn heads_grow_their_own_trails() {
+		let mut world = test_world();
+		finish_move_timer(&mut world);
+
+		SystemStage::single(spawn_two_heads.system()).run(&mut world);
+		SystemStage::single(player_movement.system()).run(&mut world);
+		SystemStage::single(player_growth.system()).run(&mut world);
+
+		let trails = world
+			.query::<(Entity, &PlayerSegments)>()
+			.iter(&world)
+			.map(|(head, segments)| (head, segments.0.clone()))
+			.collect::<Vec<(Entity, Vec<Entity>)>>();
+		assert_eq!(trails.len(), 2);
+		for (head, segments) in trails.iter() {
+			assert_eq!(segments.len(), 3);
+			assert_eq!(segments[0], *head);
+		}
+		let (_, first) = &trails[0];
+		let (_, second) = &trails[1];
+		assert!(first.iter().all(|segment| !second.contains(segment)));
+	}
+
+	#[test]
+	fn key_held_through_a_respawn_is_ignored() {
+		let mut world = test_world();
+		world.insert_resource(Time::default());
+		world.insert_resource(State::new(AppState::InGame));
+		// A wall right of the spawn point, where a held Right would steer the new head
+		for y in 0..10 {
+			world.spawn().insert(Wall).insert(Position { x: 4, y });
+		}
+		let mut keyboard_input = Input::<KeyCode>::default();
+		keyboard_input.press(KeyCode::Right);
+		keyboard_input.update();
+		world.insert_resource(keyboard_input);
+		let mut game_over_events = Events::<GameOverEvent>::default();
+		game_over_events.send(GameOverEvent);
+		world.insert_resource(game_over_events);
+
+		SystemStage::single(game_over.system()).run(&mut world);
+		world.get_resource_mut::<RespawnGrace>().unwrap().timer.tick(Duration::from_secs(2));
+		let mut input_stage = SystemStage::single(player_movement_input.system());
+		let mut movement_stage = SystemStage::single(player_movement.system());
+		for _ in 0..3 {
+			finish_move_timer(&mut world);
+			input_stage.run(&mut world);
+			movement_stage.run(&mut world);
+		}
+
+		let eliminated = world.query_filtered::<Entity, With<Eliminated>>().iter(&world).count();
+		assert_eq!(eliminated, 0);
+		let heads = world
+			.query_filtered::<&Position, With<PlayerHead>>()
+			.iter(&world)
+			.copied()
+			.collect::<Vec<Position>>();
+		assert_eq!(heads, vec![Position { x: 3, y: 6 }]);
+	}
+
+	#[test]
+	fn turns_are_checked_against_the_committed_direction() {
+		let mut head = PlayerHead::new(Direction::Up);
+		assert!(accept_turn(&head, Direction::Left));
+		head.direction = Direction::Left;
+		assert!(!accept_turn(&head, Direction::Down));
+		head.committed_direction = head.direction;
+		assert!(accept_turn(&head, Direction::Down));
+	}
+
+	#[test]
+	fn hard_is_strictly_harder_than_easy() {
+		let mut easy = GameRules::default();
+		apply_difficulty(&mut easy, Difficulty::Easy);
+		let mut hard = GameRules::default();
+		apply_difficulty(&mut hard, Difficulty::Hard);
+		assert!(hard.move_interval < easy.move_interval);
+		assert!(hard.respawn_grace < easy.respawn_grace);
+		assert!(easy.wrap_around);
+		assert!(!hard.wrap_around);
+	}
+
+	// test_world plus everything the input and round systems read, with PLAYER_NAME spawned at
+	// (3, 3) facing Up. Input starts with no keys held.
+	fn round_world() -> World {
+		let mut world = test_world();
+		world.insert_resource(Time::default());
+		world.insert_resource(State::new(AppState::InGame));
+		world.insert_resource(Input::<KeyCode>::default());
+		world.insert_resource(GameState::default());
+		world.insert_resource(RoundSummary::default());
+		world.insert_resource(Events::<GameOverEvent>::default());
+		world.insert_resource(Events::<GrowthEvent>::default());
+		SystemStage::single(spawn_player.system()).run(&mut world);
+		world
+	}
+
+	fn game_over_fired(world: &World) -> bool {
+		let events = world.get_resource::<Events<GameOverEvent>>().unwrap();
+		events.get_reader().iter(events).next().is_some()
+	}
+
+	#[test]
+	fn driving_into_a_wall_ends_the_round() {
+		let mut world = round_world();
+		world.spawn().insert(Wall).insert(Position { x: 6, y: 3 });
+		world.get_resource_mut::<Input<KeyCode>>().unwrap().press(KeyCode::Right);
+		let mut input_stage = SystemStage::single(player_movement_input.system());
+		let mut movement_stage = SystemStage::single(player_movement.system());
+		let mut round_end_stage = SystemStage::single(round_end_check.system());
+		for tick in 1..=3 {
+			assert!(!game_over_fired(&world), "game over before tick {}", tick);
+			finish_move_timer(&mut world);
+			input_stage.run(&mut world);
+			// The key stays held, but is no longer a fresh press
+			world.get_resource_mut::<Input<KeyCode>>().unwrap().update();
+			movement_stage.run(&mut world);
+			round_end_stage.run(&mut world);
+		}
+		assert!(game_over_fired(&world));
+	}
+
+	#[test]
+	fn eating_food_scores_and_grows() {
+		let mut world = round_world();
+		world.get_resource_mut::<RespawnGrace>().unwrap().timer.tick(Duration::from_secs(2));
+		let food = FoodKind::Bonus;
+		world
+			.spawn()
+			.insert(Food {
+				kind: food,
+				value: food.value(),
+			})
+			.insert(Position { x: 3, y: 5 });
+		let mut input_stage = SystemStage::single(player_movement_input.system());
+		let mut movement_stage = SystemStage::single(player_movement.system());
+		let mut growth_stage = SystemStage::single(player_growth.system());
+		let mut eating_stage = SystemStage::single(eating.system());
+		let ticks = 2;
+		for _ in 0..ticks {
+			finish_move_timer(&mut world);
+			input_stage.run(&mut world);
+			movement_stage.run(&mut world);
+			growth_stage.run(&mut world);
+			eating_stage.run(&mut world);
+		}
+
+		let (score, segments) = world
+			.query_filtered::<(&Score, &PlayerSegments), With<PlayerHead>>()
+			.iter(&world)
+			.map(|(score, segments)| (score.value, segments.0.len()))
+			.next()
+			.unwrap();
+		assert_eq!(score, food.value() as usize);
+		// Classic trails also grow by one segment on every move
+		assert_eq!(segments, MIN_TRAIL_LENGTH + ticks + food.value() as usize);
+		assert_eq!(world.query::<&Food>().iter(&world).count(), 0);
+	}
+}